@@ -1,33 +1,39 @@
 //! Builders
 
-use crate::{Esi, EsiError};
+use crate::cache::CacheStore;
+use crate::retry::RetryConfig;
+use crate::token::{TokenSet, TokenStore};
+use crate::{Credentials, Esi, EsiError};
 use reqwest::{header, Client};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::RwLock;
 
 /// Builder for the `Esi` struct.
 ///
 /// # Example
 /// ```rust
-/// # use rfesi::EsiBuilder;
+/// # use rfesi::{Credentials, EsiBuilder};
 /// let esi = EsiBuilder::new()
 ///     .user_agent("some user agent")
-///     .client_id("your_client_id")
-///     .client_secret("your_client_secret")
-///     .callback_url("your_callback_url")
+///     .credentials(Credentials::ClientCredentials {
+///         client_id: "your_client_id".to_owned(),
+///         client_secret: "your_client_secret".to_owned(),
+///         callback_url: "your_callback_url".to_owned(),
+///     })
 ///     .build()
 ///     .unwrap();
 /// ```
 #[derive(Clone, Debug, Default)]
 pub struct EsiBuilder {
     version: Option<String>,
-    client_id: Option<String>,
-    client_secret: Option<String>,
-    callback_url: Option<String>,
-    access_token: Option<String>,
-    access_expiration: Option<u64>,
-    refresh_token: Option<String>,
+    base_url: Option<String>,
+    credentials: Credentials,
     user_agent: Option<String>,
     http_timeout: Option<u64>,
+    retry: RetryConfig,
+    cache: Option<Arc<dyn CacheStore>>,
+    token_store: Option<Arc<dyn TokenStore>>,
 }
 
 impl EsiBuilder {
@@ -44,50 +50,88 @@ impl EsiBuilder {
         self
     }
 
-    pub fn client_id(mut self, val: &str) -> Self {
-        self.client_id = Some(val.to_owned());
+    /// Override the base URL requests are sent to.
+    ///
+    /// Defaults to ESI's production URL; mainly useful for pointing a
+    /// client at a local mock server in tests (see the `test-util`
+    /// feature's [`crate::mock`] module).
+    pub fn base_url(mut self, val: &str) -> Self {
+        self.base_url = Some(val.to_owned());
         self
     }
 
-    pub fn client_secret(mut self, val: &str) -> Self {
-        self.client_secret = Some(val.to_owned());
+    /// Set the authentication mode this client should use. See
+    /// [`Credentials`] for the available modes.
+    ///
+    /// Defaults to `Credentials::None`, i.e. only `RequestType::Public`
+    /// endpoints will work.
+    pub fn credentials(mut self, val: Credentials) -> Self {
+        self.credentials = val;
         self
     }
 
-    pub fn callback_url(mut self, val: &str) -> Self {
-        self.callback_url = Some(val.to_owned());
+    pub fn user_agent(mut self, val: &str) -> Self {
+        self.user_agent = Some(val.to_owned());
         self
     }
 
-    pub fn access_token(mut self, val: Option<&str>) -> Self {
-        self.access_token = val.map(|v| v.to_owned());
+    /// Set the timeout to use in millis when sending HTTP requests.
+    ///
+    /// Will default to 60,000 (1 minute) if not set.
+    pub fn http_timeout(mut self, val: Option<u64>) -> Self {
+        self.http_timeout = val;
         self
     }
 
-    pub fn access_expiration(mut self, val: Option<u64>) -> Self {
-        self.access_expiration = val;
+    /// Set the maximum number of retries for transient failures and ESI's
+    /// error-limit budget (not counting the initial attempt).
+    ///
+    /// Defaults to 3.
+    pub fn max_retries(mut self, val: u32) -> Self {
+        self.retry.max_retries = val;
         self
     }
 
-    pub fn refresh_token(mut self, val: Option<&str>) -> Self {
-        self.refresh_token = val.map(|v| v.to_owned());
+    /// Whether to back off when ESI's error-limit budget (`X-ESI-Error-Limit-Remain`)
+    /// is low or a `420` is returned, sleeping until `X-ESI-Error-Limit-Reset`
+    /// elapses before the next attempt.
+    ///
+    /// Defaults to `true`.
+    pub fn retry_on_error_limit(mut self, val: bool) -> Self {
+        self.retry.retry_on_error_limit = val;
         self
     }
 
-    pub fn user_agent(mut self, val: &str) -> Self {
-        self.user_agent = Some(val.to_owned());
+    /// Whether the non-idempotent `post_characters_affiliation` call may
+    /// also be retried. All GETs are retried regardless of this setting.
+    ///
+    /// Defaults to `false`.
+    pub fn retry_posts(mut self, val: bool) -> Self {
+        self.retry.retry_posts = val;
         self
     }
 
-    /// Set the timeout to use in millis when sending HTTP requests.
+    /// Enable `ETag`-based conditional-request caching for GET requests,
+    /// backed by the given [`CacheStore`].
     ///
-    /// Will default to 60,000 (1 minute) if not set.
-    pub fn http_timeout(mut self, val: Option<u64>) -> Self {
-        self.http_timeout = val;
+    /// Not set by default, i.e. caching is disabled.
+    pub fn cache(mut self, val: impl CacheStore + 'static) -> Self {
+        self.cache = Some(Arc::new(val));
+        self
+    }
+
+    /// Persist refreshed tokens through the given [`TokenStore`], and load
+    /// a previously persisted token set from it if the builder's
+    /// `credentials` carry no access token (i.e. are not
+    /// `Credentials::AccessToken`).
+    ///
+    /// Not set by default, i.e. tokens only live for the process lifetime.
+    pub fn token_store(mut self, val: impl TokenStore + 'static) -> Self {
+        self.token_store = Some(Arc::new(val));
         self
     }
 
-    fn construct_client(&self, _access_token: Option<&str>) -> Result<Client, EsiError> {
+    fn construct_client(&self) -> Result<Client, EsiError> {
         let http_timeout = self
             .http_timeout
             .map(Duration::from_millis)
@@ -107,9 +151,6 @@ impl EsiBuilder {
                 header::ACCEPT,
                 header::HeaderValue::from_static("application/json"),
             );
-
-            // TODO insert token header if present
-
             map
         };
         let client = Client::builder()
@@ -119,28 +160,43 @@ impl EsiBuilder {
         Ok(client)
     }
 
+    /// Determine the starting token set: whatever `self.credentials` starts
+    /// out with, falling back to whatever `token_store` has persisted.
+    fn initial_tokens(&self) -> Result<TokenSet, EsiError> {
+        let explicit = self.credentials.initial_tokens();
+        if explicit.access_token.is_some() {
+            return Ok(explicit);
+        }
+        if let Some(store) = &self.token_store {
+            if let Some(loaded) = store.load()? {
+                return Ok(loaded);
+            }
+        }
+        Ok(explicit)
+    }
+
     /// Construct the `Esi` instance, consuming the builder.
     ///
-    /// There are a few things that could go wrong, like
-    /// not setting one of the mandatory fields or providing a user
-    /// agent that is not a valid HTTP header value.
+    /// There are a few things that could go wrong, like providing a user
+    /// agent that is not a valid HTTP header value. Unlike `credentials`,
+    /// which is validated by the type system ([`Credentials::ClientCredentials`]
+    /// can't be constructed with missing fields), a missing `user_agent` is
+    /// the one runtime mandatory-field error left.
     pub fn build(self) -> Result<Esi, EsiError> {
-        let client = self.construct_client(None)?;
+        let client = self.construct_client()?;
+        let tokens = self.initial_tokens()?;
         let e = Esi {
             version: self.version.unwrap_or_else(|| "latest".to_owned()),
-            client_id: self
-                .client_id
-                .ok_or_else(|| EsiError::EmptyClientValue("client_id".to_owned()))?,
-            client_secret: self
-                .client_secret
-                .ok_or_else(|| EsiError::EmptyClientValue("client_secret".to_owned()))?,
-            callback_url: self
-                .callback_url
-                .ok_or_else(|| EsiError::EmptyClientValue("callback_url".to_owned()))?,
-            access_token: self.access_token,
-            access_expiration: self.access_expiration,
-            refresh_token: self.refresh_token,
+            base_url: self
+                .base_url
+                .unwrap_or_else(|| crate::DEFAULT_BASE_URL.to_owned()),
+            credentials: self.credentials,
+            tokens: RwLock::new(tokens),
+            refresh_lock: tokio::sync::Mutex::new(()),
+            token_store: self.token_store,
             client,
+            retry: self.retry,
+            cache: self.cache,
         };
         Ok(e)
     }
@@ -149,22 +205,24 @@ impl EsiBuilder {
 #[cfg(test)]
 mod tests {
     use super::EsiBuilder;
+    use crate::Credentials;
 
     #[test]
     fn test_builder_valid() {
         let b = EsiBuilder::new()
-            .client_id("a")
-            .client_secret("b")
-            .callback_url("c")
+            .credentials(Credentials::ClientCredentials {
+                client_id: "a".to_owned(),
+                client_secret: "b".to_owned(),
+                callback_url: "c".to_owned(),
+            })
             .user_agent("d")
             .build()
             .unwrap();
 
-        assert_eq!(b.client_id, "a");
-        assert_eq!(b.client_secret, "b");
-        assert_eq!(b.callback_url, "c");
+        assert_eq!(b.credentials.client_id(), Some("a"));
+        assert_eq!(b.credentials.client_secret(), Some("b"));
         assert_eq!(b.version, "latest");
-        assert_eq!(b.access_token, None);
+        assert_eq!(b.tokens.try_read().unwrap().access_token, None);
     }
 
     #[test]
@@ -174,4 +232,10 @@ mod tests {
         let s = format!("{}", res.unwrap_err());
         assert_eq!(s, "Missing `Esi` struct value 'user_agent'");
     }
+
+    #[test]
+    fn test_builder_defaults_to_no_credentials() {
+        let b = EsiBuilder::new().user_agent("d").build().unwrap();
+        assert!(matches!(b.credentials, Credentials::None));
+    }
 }
@@ -0,0 +1,83 @@
+//! ETag-based conditional-request caching for GET responses.
+//!
+//! ESI responses carry `ETag` and `Expires` headers and support conditional
+//! `If-None-Match` requests that return `304 Not Modified` without counting
+//! heavily against the error-limit budget. [`Esi::get_response`] consults a
+//! configured [`CacheStore`] before sending a GET, and revalidates or
+//! refreshes the stored entry afterwards.
+//!
+//! [`Esi::get_response`]: crate::Esi::get_response
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A single cached response: its headers, raw body, the `ETag` that produced
+/// it, and when it stops being servable without revalidation.
+///
+/// `headers` is captured so a fresh-cache-hit can be served without a network
+/// call while still exposing response headers like `X-Pages` to callers that
+/// need them (e.g. `api_get_paged!`).
+#[derive(Clone, Debug)]
+pub struct CacheEntry {
+    /// The `ETag` value returned alongside `body`.
+    pub etag: String,
+    /// The response headers returned alongside `body`.
+    pub headers: HeaderMap,
+    /// The raw (JSON) response body.
+    pub body: Vec<u8>,
+    /// Unix timestamp after which this entry must be revalidated.
+    pub expires_at: u64,
+}
+
+/// Storage for [`CacheEntry`] values, keyed by the final request URL
+/// (including query parameters such as `page`).
+///
+/// An in-memory default is provided via [`InMemoryCacheStore`]; implement
+/// this trait to persist entries elsewhere (e.g. to disk or Redis).
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    /// Look up a previously stored entry for `key`.
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+    /// Store (or replace) the entry for `key`.
+    fn put(&self, key: &str, entry: CacheEntry);
+}
+
+/// Default, process-local [`CacheStore`] backed by a `HashMap`.
+#[derive(Debug, Default)]
+pub struct InMemoryCacheStore {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl CacheStore for InMemoryCacheStore {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        self.entries.lock().unwrap().insert(key.to_owned(), entry);
+    }
+}
+
+/// Current unix timestamp, used to compare against [`CacheEntry::expires_at`].
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Parse an HTTP `Expires` header value into a unix timestamp, falling back
+/// to `fallback_secs` from now if the header is missing or unparseable.
+pub(crate) fn parse_expires(value: Option<&HeaderValue>, fallback_secs: u64) -> u64 {
+    value
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| httpdate::parse_http_date(v).ok())
+        .map(|t| {
+            t.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        })
+        .unwrap_or_else(|| now() + fallback_secs)
+}
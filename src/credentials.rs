@@ -0,0 +1,72 @@
+//! Typed authentication modes for an [`Esi`](crate::Esi) client.
+
+use crate::token::TokenSet;
+
+/// Which authentication mode an `Esi` client was built with.
+///
+/// This determines what `RequestType::Authenticated` endpoints can do:
+/// `None` can only ever reach `RequestType::Public` endpoints (calling an
+/// authenticated one returns [`EsiError::NotAuthenticated`](crate::EsiError::NotAuthenticated)).
+/// `AccessToken` carries a bearer token, and optionally a refresh token to
+/// renew it. `ClientCredentials` supplies the client id/secret sent when
+/// refreshing, but carries no token itself: authenticated calls also return
+/// `NotAuthenticated` unless a token set is loaded from a `TokenStore`, as
+/// the authorization-code exchange is not performed by this crate.
+#[derive(Clone, Debug, Default)]
+pub enum Credentials {
+    /// No credentials at all; public-only, e.g. `get_public_info`.
+    #[default]
+    None,
+    /// A previously obtained access token, optionally with a refresh token
+    /// to renew it once it expires.
+    AccessToken {
+        /// The current access token.
+        token: String,
+        /// Unix timestamp at which `token` expires, if known.
+        expiry: Option<u64>,
+        /// Refresh token, if renewal is possible.
+        refresh: Option<String>,
+    },
+    /// The SSO client id/secret/callback used to complete the OAuth flow
+    /// and to refresh tokens thereafter.
+    ClientCredentials {
+        /// The application's SSO client id.
+        client_id: String,
+        /// The application's SSO client secret.
+        client_secret: String,
+        /// The registered OAuth callback URL.
+        callback_url: String,
+    },
+}
+
+impl Credentials {
+    /// The token set this mode starts out with.
+    pub(crate) fn initial_tokens(&self) -> TokenSet {
+        match self {
+            Credentials::AccessToken {
+                token,
+                expiry,
+                refresh,
+            } => TokenSet {
+                access_token: Some(token.clone()),
+                access_expiration: *expiry,
+                refresh_token: refresh.clone(),
+            },
+            Credentials::None | Credentials::ClientCredentials { .. } => TokenSet::default(),
+        }
+    }
+
+    pub(crate) fn client_id(&self) -> Option<&str> {
+        match self {
+            Credentials::ClientCredentials { client_id, .. } => Some(client_id),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn client_secret(&self) -> Option<&str> {
+        match self {
+            Credentials::ClientCredentials { client_secret, .. } => Some(client_secret),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Errors
+
+use reqwest::header::InvalidHeaderValue;
+use thiserror::Error;
+
+/// Errors that can be returned from this crate.
+#[derive(Debug, Error)]
+pub enum EsiError {
+    /// A mandatory field on `EsiBuilder` was not set before calling `build`.
+    #[error("Missing `Esi` struct value '{0}'")]
+    EmptyClientValue(String),
+
+    /// The underlying HTTP client returned an error.
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    /// A provided value was not valid as an HTTP header.
+    #[error(transparent)]
+    InvalidHeaderValue(#[from] InvalidHeaderValue),
+
+    /// A response (or cached) body could not be deserialized as JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// Reading or writing a persisted token/cache file failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// A `RequestType::Authenticated` endpoint was called on a client that
+    /// has neither a non-empty access token nor a refresh token, e.g. one
+    /// built with `Credentials::None`.
+    #[error("this endpoint requires authentication, but the client has no access or refresh token")]
+    NotAuthenticated,
+}
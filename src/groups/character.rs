@@ -70,12 +70,16 @@ impl<'a> CharacterGroup<'a> {
         (character_id: u64) => "{character_id}"
     );
 
-    api_get!(
+    api_get_paged!(
         /// Get a character's corporation history.
+        ///
+        /// Fetches every page reported by the `X-Pages` response header and
+        /// returns the concatenated result.
         get_history,
+        get_history_stream,
         "get_characters_character_id_corporationhistory",
         RequestType::Public,
-        Vec<CharacterCorporationHistoryItem>,
+        CharacterCorporationHistoryItem,
         (character_id: u64) => "{character_id}"
     );
 
@@ -0,0 +1,3 @@
+//! Endpoint groups, one module per ESI tag.
+
+pub mod character;
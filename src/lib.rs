@@ -0,0 +1,370 @@
+//! # rfesi
+//!
+//! Rust bindings for [EVE Online's ESI API](https://esi.evetech.net/ui/).
+
+mod builders;
+mod cache;
+mod credentials;
+mod errors;
+#[macro_use]
+mod macros;
+pub mod groups;
+#[cfg(feature = "test-util")]
+pub mod mock;
+mod paths;
+mod retry;
+mod token;
+
+pub use builders::EsiBuilder;
+pub use cache::{CacheEntry, CacheStore, InMemoryCacheStore};
+pub use credentials::Credentials;
+pub use errors::EsiError;
+pub use token::{FileTokenStore, TokenSet, TokenStore};
+
+use groups::character::CharacterGroup;
+use reqwest::header::{HeaderMap, ETAG, IF_NONE_MATCH};
+use reqwest::{Client, Method, StatusCode};
+use retry::RetryConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use token::{SsoTokenResponse, SSO_TOKEN_URL};
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::sleep;
+
+/// Fallback freshness window applied when a response has an `ETag` but no
+/// (or an unparseable) `Expires` header.
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Default base URL for all ESI requests; overridable via
+/// `EsiBuilder::base_url` (primarily so the `test-util` mock backend can
+/// point a client at itself instead).
+pub(crate) const DEFAULT_BASE_URL: &str = "https://esi.evetech.net";
+
+/// Whether a given endpoint requires an authenticated (token-bearing) request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestType {
+    /// The endpoint works without any authentication.
+    Public,
+    /// The endpoint requires a valid access token.
+    Authenticated,
+}
+
+/// The core client used to interact with ESI.
+#[derive(Debug)]
+pub struct Esi {
+    pub(crate) version: String,
+    pub(crate) base_url: String,
+    pub(crate) credentials: Credentials,
+    pub(crate) tokens: RwLock<TokenSet>,
+    /// Serializes token refreshes so concurrent callers (e.g. the parallel
+    /// page fetches in `api_get_paged!`) don't race on the same
+    /// single-use, rotating EVE SSO refresh token.
+    pub(crate) refresh_lock: Mutex<()>,
+    pub(crate) token_store: Option<Arc<dyn TokenStore>>,
+    pub(crate) client: Client,
+    pub(crate) retry: RetryConfig,
+    pub(crate) cache: Option<Arc<dyn CacheStore>>,
+}
+
+/// A GET response body paired with its headers, decoupled from the live
+/// `reqwest::Response` so that a cache hit can be served without ever
+/// opening a connection.
+pub(crate) struct EsiResponse {
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl EsiResponse {
+    pub(crate) fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub(crate) async fn json<T: DeserializeOwned>(self) -> Result<T, EsiError> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+/// Whether a stored access token is actually usable — an empty string (e.g.
+/// from a `Credentials::AccessToken { token: "".into(), .. }` misconfiguration)
+/// must not be treated as a valid, already-fresh token.
+fn is_usable_token(token: &Option<String>) -> bool {
+    token.as_deref().is_some_and(|t| !t.is_empty())
+}
+
+impl Esi {
+    /// Endpoints under the `/characters` group.
+    pub fn group_character(&self) -> CharacterGroup {
+        CharacterGroup { esi: self }
+    }
+
+    /// Build the full URL for a single ESI operation, inserting the already
+    /// formatted dynamic path segment (if any) between the operation's
+    /// static prefix and suffix.
+    pub(crate) fn build_url(&self, operation_id: &str, dynamic: &str) -> String {
+        let (prefix, suffix) = paths::path_for_operation(operation_id);
+        format!(
+            "{}/{}{prefix}{dynamic}{suffix}",
+            self.base_url, self.version
+        )
+    }
+
+    /// Return a valid access token, refreshing it first if `force` is set or
+    /// the current one has passed `access_expiration`.
+    ///
+    /// Fails with [`EsiError::NotAuthenticated`] if there is no access or
+    /// refresh token to work with at all — e.g. a `Credentials::None` or a
+    /// bare `Credentials::ClientCredentials` client, neither of which this
+    /// crate can turn into a token on its own (only refresh-token renewal is
+    /// implemented, not the initial authorization-code exchange).
+    async fn ensure_fresh_token(&self, force: bool) -> Result<Option<String>, EsiError> {
+        if let Some(token) = self.fresh_token(force).await {
+            return Ok(Some(token));
+        }
+        let has_usable_token = {
+            let tokens = self.tokens.read().await;
+            is_usable_token(&tokens.access_token) || tokens.refresh_token.is_some()
+        };
+        if !has_usable_token {
+            return Err(EsiError::NotAuthenticated);
+        }
+
+        // Serialize refreshes: EVE SSO refresh tokens are single-use and
+        // rotate on every exchange, so concurrent callers (e.g. the
+        // parallel page fetches in `api_get_paged!`) racing on the same
+        // stale refresh token would invalidate each other. Holding this
+        // lock across the whole refresh means only one request is ever
+        // in flight at a time; everyone else just waits for its result.
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(token) = self.fresh_token(force).await {
+            return Ok(Some(token));
+        }
+        self.refresh_access_token().await
+    }
+
+    /// Return the current access token if it's still usable, without
+    /// attempting a refresh.
+    async fn fresh_token(&self, force: bool) -> Option<String> {
+        let tokens = self.tokens.read().await;
+        let fresh = !force
+            && is_usable_token(&tokens.access_token)
+            && tokens
+                .access_expiration
+                .map_or(true, |exp| cache::now() < exp);
+        fresh.then(|| tokens.access_token.clone()).flatten()
+    }
+
+    /// Exchange the stored refresh token for a new access token against EVE
+    /// SSO, persisting the result through `token_store` if one is set.
+    async fn refresh_access_token(&self) -> Result<Option<String>, EsiError> {
+        let refresh_token = self.tokens.read().await.refresh_token.clone();
+        let Some(refresh_token) = refresh_token else {
+            return Ok(self.tokens.read().await.access_token.clone());
+        };
+
+        let mut req = self.client.post(SSO_TOKEN_URL).form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ]);
+        if let (Some(client_id), Some(client_secret)) =
+            (self.credentials.client_id(), self.credentials.client_secret())
+        {
+            req = req.basic_auth(client_id, Some(client_secret));
+        }
+        let sso_resp: SsoTokenResponse = req.send().await?.error_for_status()?.json().await?;
+
+        let new_tokens = TokenSet {
+            access_token: Some(sso_resp.access_token),
+            access_expiration: Some(cache::now() + sso_resp.expires_in),
+            refresh_token: Some(sso_resp.refresh_token.unwrap_or(refresh_token)),
+        };
+        *self.tokens.write().await = new_tokens.clone();
+        if let Some(store) = &self.token_store {
+            store.save(&new_tokens)?;
+        }
+        Ok(new_tokens.access_token)
+    }
+
+    /// Issue a single GET request against the given ESI operation and
+    /// deserialize the JSON response body.
+    pub(crate) async fn request<T: DeserializeOwned>(
+        &self,
+        operation_id: &str,
+        request_type: RequestType,
+        dynamic: &str,
+    ) -> Result<T, EsiError> {
+        let resp = self.get_response(operation_id, request_type, dynamic, None).await?;
+        resp.json::<T>().await
+    }
+
+    /// Issue a single GET request against the given ESI operation, optionally
+    /// for a specific 1-indexed page, and return the response body/headers
+    /// so callers can inspect headers such as `X-Pages` before consuming the
+    /// body.
+    ///
+    /// If a [`CacheStore`] is configured, a fresh cache entry is served
+    /// without a network call; a stale one is revalidated with
+    /// `If-None-Match` and, on `304 Not Modified`, replayed from cache with
+    /// its expiry refreshed. GETs are idempotent, so this always retries
+    /// transient failures and the ESI error-limit budget per [`RetryConfig`].
+    pub(crate) async fn get_response(
+        &self,
+        operation_id: &str,
+        request_type: RequestType,
+        dynamic: &str,
+        page: Option<u32>,
+    ) -> Result<EsiResponse, EsiError> {
+        let url = self.build_url(operation_id, dynamic);
+        let cache_key = match page {
+            Some(page) => format!("{url}?page={page}"),
+            None => url.clone(),
+        };
+
+        let cached = self.cache.as_ref().and_then(|c| c.get(&cache_key));
+        if let Some(entry) = &cached {
+            if entry.expires_at > cache::now() {
+                return Ok(EsiResponse {
+                    headers: entry.headers.clone(),
+                    body: entry.body.clone(),
+                });
+            }
+        }
+
+        let resp = self
+            .send_with_retry(true, request_type, |token| {
+                let mut req = self.client.request(Method::GET, url.clone());
+                if let Some(page) = page {
+                    req = req.query(&[("page", page)]);
+                }
+                if let Some(token) = token {
+                    req = req.bearer_auth(token);
+                }
+                if let Some(entry) = &cached {
+                    req = req.header(IF_NONE_MATCH, &entry.etag);
+                }
+                req
+            })
+            .await?;
+
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            let entry = cached.expect("304 Not Modified without a cached entry to revalidate");
+            let resp_headers = resp.headers().clone();
+            if let Some(store) = &self.cache {
+                let expires_at = cache::parse_expires(resp_headers.get(reqwest::header::EXPIRES), DEFAULT_CACHE_TTL_SECS);
+                store.put(
+                    &cache_key,
+                    CacheEntry {
+                        headers: resp_headers.clone(),
+                        expires_at,
+                        ..entry.clone()
+                    },
+                );
+            }
+            return Ok(EsiResponse {
+                headers: resp_headers,
+                body: entry.body,
+            });
+        }
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?.to_vec();
+        if let (Some(store), Some(etag)) = (&self.cache, headers.get(ETAG)) {
+            let expires_at = cache::parse_expires(headers.get(reqwest::header::EXPIRES), DEFAULT_CACHE_TTL_SECS);
+            store.put(
+                &cache_key,
+                CacheEntry {
+                    etag: etag.to_str().unwrap_or_default().to_owned(),
+                    headers: headers.clone(),
+                    body: body.clone(),
+                    expires_at,
+                },
+            );
+        }
+        Ok(EsiResponse { headers, body })
+    }
+
+    /// Send a request built fresh by `build_request` for each attempt,
+    /// retrying on transient failures and the ESI error-limit budget up to
+    /// `self.retry.max_retries` times. Non-GET callers must pass
+    /// `idempotent: false`, which only retries transient and error-limit
+    /// failures when `retry_posts` is set.
+    ///
+    /// For `RequestType::Authenticated`, `build_request` is handed a fresh
+    /// access token (refreshing it first if needed); a `401` forces a
+    /// refresh before the next attempt, whether or not the request is
+    /// idempotent, and counts against `max_retries` like any other retry.
+    async fn send_with_retry(
+        &self,
+        idempotent: bool,
+        request_type: RequestType,
+        build_request: impl Fn(Option<&str>) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, EsiError> {
+        let may_retry = idempotent || self.retry.retry_posts;
+        let mut attempt = 0;
+        let mut force_refresh = false;
+        loop {
+            let token = match request_type {
+                RequestType::Authenticated => self.ensure_fresh_token(force_refresh).await?,
+                RequestType::Public => None,
+            };
+            force_refresh = false;
+            let resp = build_request(token.as_deref()).send().await?;
+
+            if matches!(request_type, RequestType::Authenticated)
+                && resp.status() == StatusCode::UNAUTHORIZED
+                && attempt < self.retry.max_retries
+            {
+                force_refresh = true;
+                attempt += 1;
+                continue;
+            }
+            if may_retry && !resp.status().is_success() {
+                if let Some(wait) = self.retry.retry_on_error_limit.then(|| retry::error_limit_wait(&resp)).flatten() {
+                    if attempt < self.retry.max_retries {
+                        sleep(wait).await;
+                        attempt += 1;
+                        continue;
+                    }
+                } else if retry::is_transient(resp.status()) && attempt < self.retry.max_retries {
+                    sleep(retry::backoff_delay(attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+            }
+            return Ok(resp.error_for_status()?);
+        }
+    }
+
+    /// Issue a single POST request with a JSON body against the given ESI
+    /// operation and deserialize the JSON response body.
+    ///
+    /// POSTs are not idempotent in general, so transient and error-limit
+    /// failures are only retried when `RetryConfig::retry_posts` is enabled
+    /// (e.g. for the affiliation lookup, which is safe to repeat). A `401`
+    /// on an authenticated POST is still retried once the token is refreshed.
+    pub(crate) async fn request_with_body<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        operation_id: &str,
+        request_type: RequestType,
+        dynamic: &str,
+        body: &B,
+    ) -> Result<T, EsiError> {
+        let resp = self
+            .send_with_retry(false, request_type, |token| {
+                let url = self.build_url(operation_id, dynamic);
+                let mut req = self.client.request(Method::POST, url).json(body);
+                if let Some(token) = token {
+                    req = req.bearer_auth(token);
+                }
+                req
+            })
+            .await?;
+        Ok(resp.json::<T>().await?)
+    }
+}
+
+pub(crate) mod prelude {
+    pub(crate) use crate::errors::EsiError;
+    pub(crate) use crate::{Esi, RequestType};
+    pub(crate) use serde::Deserialize;
+}
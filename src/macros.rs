@@ -0,0 +1,135 @@
+//! Macros for generating endpoint wrapper methods on the various `*Group`
+//! structs under [`crate::groups`].
+
+/// Generate a method that issues a single GET request against an ESI
+/// operation and deserializes the JSON response body.
+macro_rules! api_get {
+    (
+        $(#[$meta:meta])*
+        $fn_name:ident,
+        $operation_id:expr,
+        $request_type:expr,
+        $return_type:ty,
+        ($($arg_name:ident: $arg_type:ty),*) => $path_fmt:expr
+    ) => {
+        $(#[$meta])*
+        pub async fn $fn_name(&self, $($arg_name: $arg_type),*) -> Result<$return_type, EsiError> {
+            let dynamic = format!($path_fmt, $($arg_name = $arg_name),*);
+            self.esi.request($operation_id, $request_type, &dynamic).await
+        }
+    };
+}
+
+/// Generate a method that issues a single POST request with a JSON body
+/// against an ESI operation and deserializes the JSON response body.
+macro_rules! api_post {
+    (
+        $(#[$meta:meta])*
+        $fn_name:ident,
+        $operation_id:expr,
+        $request_type:expr,
+        $return_type:ty,
+        $(($($arg_name:ident: $arg_type:ty),*) => $path_fmt:expr)?,
+        $($body_name:ident: $body_type:ty),* $(,)?
+    ) => {
+        $(#[$meta])*
+        pub async fn $fn_name(&self, $($($arg_name: $arg_type,)*)? $($body_name: $body_type),*) -> Result<$return_type, EsiError> {
+            let dynamic = String::new();
+            $(let dynamic = format!($path_fmt, $($arg_name = $arg_name),*);)?
+            self.esi
+                .request_with_body($operation_id, $request_type, &dynamic, &($($body_name),*))
+                .await
+        }
+    };
+}
+
+/// Generate a method (and a streaming sibling) that fetches every page of a
+/// paginated ESI list endpoint (one that returns an `X-Pages` response
+/// header) and concatenates the results in page order.
+///
+/// The first page is fetched to discover the total page count; any
+/// remaining pages are then fetched concurrently. The streaming sibling
+/// instead yields one page at a time, so callers don't have to buffer the
+/// full collection in memory.
+macro_rules! api_get_paged {
+    (
+        $(#[$meta:meta])*
+        $fn_name:ident,
+        $stream_fn_name:ident,
+        $operation_id:expr,
+        $request_type:expr,
+        $item_type:ty,
+        ($($arg_name:ident: $arg_type:ty),*) => $path_fmt:expr
+    ) => {
+        $(#[$meta])*
+        pub async fn $fn_name(&self, $($arg_name: $arg_type),*) -> Result<Vec<$item_type>, EsiError> {
+            let dynamic = format!($path_fmt, $($arg_name = $arg_name),*);
+            let first = self
+                .esi
+                .get_response($operation_id, $request_type, &dynamic, Some(1))
+                .await?;
+            let total_pages = first
+                .headers()
+                .get("x-pages")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(1);
+            let mut items: Vec<$item_type> = first.json().await?;
+            if total_pages > 1 {
+                let rest = futures::future::try_join_all((2..=total_pages).map(|page| {
+                    let dynamic = dynamic.clone();
+                    async move {
+                        let resp = self
+                            .esi
+                            .get_response($operation_id, $request_type, &dynamic, Some(page))
+                            .await?;
+                        resp.json::<Vec<$item_type>>().await.map_err(EsiError::from)
+                    }
+                }))
+                .await?;
+                for page_items in rest {
+                    items.extend(page_items);
+                }
+            }
+            Ok(items)
+        }
+
+        $(#[$meta])*
+        ///
+        /// Streams one page at a time instead of buffering the whole
+        /// collection; useful for endpoints that can return very large
+        /// lists (e.g. asset listings).
+        pub fn $stream_fn_name(
+            &self,
+            $($arg_name: $arg_type),*
+        ) -> impl futures::Stream<Item = Result<Vec<$item_type>, EsiError>> + '_ {
+            let dynamic = format!($path_fmt, $($arg_name = $arg_name),*);
+            futures::stream::unfold(Some(1u32), move |page| {
+                let dynamic = dynamic.clone();
+                async move {
+                    let page = page?;
+                    let resp = match self
+                        .esi
+                        .get_response($operation_id, $request_type, &dynamic, Some(page))
+                        .await
+                    {
+                        Ok(resp) => resp,
+                        Err(e) => return Some((Err(e), None)),
+                    };
+                    let total_pages = resp
+                        .headers()
+                        .get("x-pages")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u32>().ok())
+                        .unwrap_or(1);
+                    let items = match resp.json::<Vec<$item_type>>().await {
+                        Ok(items) => items,
+                        Err(e) => return Some((Err(EsiError::from(e)), None)),
+                    };
+                    let next_page = if page < total_pages { Some(page + 1) } else { None };
+                    Some((Ok(items), next_page))
+                }
+            })
+        }
+    };
+}
@@ -0,0 +1,65 @@
+//! A local mock ESI backend for testing endpoint wrappers without hitting
+//! live ESI. Enabled via the `test-util` feature.
+//!
+//! Mirrors the mock-server helpers found in other reqwest-based API
+//! clients: register canned JSON responses (with whatever headers you
+//! like, e.g. `X-Pages`, `ETag`, or the ESI error-limit headers) keyed by
+//! path and method, then hand out an [`EsiBuilder`] already pointed at the
+//! server.
+
+use crate::EsiBuilder;
+use httpmock::{Method, MockServer};
+use serde::Serialize;
+
+/// A local HTTP server standing in for `https://esi.evetech.net`.
+pub struct MockEsiBackend {
+    server: MockServer,
+}
+
+impl MockEsiBackend {
+    /// Start a new mock server on a random local port.
+    pub fn start() -> Self {
+        Self {
+            server: MockServer::start(),
+        }
+    }
+
+    /// Register a canned JSON response for `method` + `path` + `query`
+    /// (e.g. `&[("page", "2")]`, or `&[]` to ignore query parameters), with
+    /// any extra response headers (e.g. `("X-Pages", "3")`,
+    /// `("ETag", "\"v1\"")`, `("X-ESI-Error-Limit-Remain", "2")`).
+    pub fn mock_json(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        status: u16,
+        body: &impl Serialize,
+        headers: &[(&str, &str)],
+    ) {
+        self.server.mock(|when, then| {
+            let mut when = when.method(method).path(path);
+            for (name, value) in query {
+                when = when.query_param(*name, *value);
+            }
+            let mut then = then.status(status).json_body_obj(body);
+            for (name, value) in headers {
+                then = then.header(*name, *value);
+            }
+        });
+    }
+
+    /// Direct access to the underlying [`MockServer`] for scenarios
+    /// `mock_json` doesn't cover, e.g. matching on request headers or
+    /// varying the response across successive calls to the same route.
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// An `EsiBuilder` pre-configured to send every request to this server
+    /// instead of live ESI. Callers still need to set `user_agent` and
+    /// `credentials` as usual.
+    pub fn esi_builder(&self) -> EsiBuilder {
+        EsiBuilder::new().base_url(&self.server.base_url())
+    }
+}
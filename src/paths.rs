@@ -0,0 +1,20 @@
+//! Static mapping from ESI `operationId`s to the prefix/suffix around their
+//! path parameters.
+//!
+//! The dynamic portion of the path (e.g. `{character_id}`) is filled in by the
+//! caller and sandwiched between the two halves returned here, e.g. for
+//! `"get_characters_character_id_corporationhistory"` this yields
+//! `("/characters/", "/corporationhistory/")`, which combines with a
+//! formatted `"5"` into `/characters/5/corporationhistory/`.
+pub(crate) fn path_for_operation(operation_id: &str) -> (&'static str, &'static str) {
+    match operation_id {
+        "get_characters_character_id" => ("/characters/", "/"),
+        "get_characters_character_id_corporationhistory" => {
+            ("/characters/", "/corporationhistory/")
+        }
+        "get_characters_character_id_portrait" => ("/characters/", "/portrait/"),
+        "post_characters_affiliation" => ("/characters/affiliation/", ""),
+        "get_characters_character_id_blueprints" => ("/characters/", "/blueprints/"),
+        _ => panic!("rfesi: unknown ESI operation id `{operation_id}`"),
+    }
+}
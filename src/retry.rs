@@ -0,0 +1,82 @@
+//! Retry support for transient failures and ESI's error-limit budget.
+//!
+//! ESI enforces an error budget surfaced through the
+//! `X-ESI-Error-Limit-Remain` and `X-ESI-Error-Limit-Reset` response headers,
+//! and returns HTTP 420 once that budget is exhausted. This module provides
+//! the backoff math; the actual retry loop is `Esi::send_with_retry`, which
+//! both [`crate::Esi::get_response`] and [`crate::Esi::request_with_body`]
+//! go through.
+
+use rand::Rng;
+use reqwest::{Response, StatusCode};
+use std::time::Duration;
+
+const BASE_BACKOFF: Duration = Duration::from_millis(200);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Once `X-ESI-Error-Limit-Remain` drops to or below this, treat the budget
+/// as exhausted even before ESI starts returning 420s.
+const ERROR_LIMIT_LOW_WATER_MARK: u32 = 5;
+
+/// Retry behavior applied around outgoing requests.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RetryConfig {
+    /// Maximum number of retries (not counting the initial attempt).
+    pub(crate) max_retries: u32,
+    /// Whether to back off when the ESI error-limit budget is low or a 420
+    /// is returned.
+    pub(crate) retry_on_error_limit: bool,
+    /// Whether the (non-idempotent) `post_characters_affiliation` call may
+    /// also be retried. All GETs are retried regardless of this flag.
+    pub(crate) retry_posts: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            retry_on_error_limit: true,
+            retry_posts: false,
+        }
+    }
+}
+
+/// Full-jitter exponential backoff delay for the given zero-indexed attempt:
+/// a random duration in `[0, min(cap, base * 2^attempt)]`.
+pub(crate) fn backoff_delay(attempt: u32) -> Duration {
+    let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+    let capped = BASE_BACKOFF
+        .saturating_mul(factor)
+        .min(MAX_BACKOFF)
+        .as_millis() as u64;
+    let millis = rand::thread_rng().gen_range(0..=capped);
+    Duration::from_millis(millis)
+}
+
+/// Whether the given status code represents a transient failure worth a
+/// plain backoff-and-retry (429/5xx; 420 is handled via
+/// [`error_limit_wait`] instead since it carries its own reset hint).
+pub(crate) fn is_transient(status: StatusCode) -> bool {
+    status.as_u16() == 429 || status.is_server_error()
+}
+
+/// If `resp` indicates the ESI error-limit budget is exhausted or close to
+/// it, return how long to wait before the next attempt.
+pub(crate) fn error_limit_wait(resp: &Response) -> Option<Duration> {
+    let headers = resp.headers();
+    let remaining: Option<u32> = headers
+        .get("x-esi-error-limit-remain")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let reset: Option<u64> = headers
+        .get("x-esi-error-limit-reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let budget_exhausted =
+        resp.status().as_u16() == 420 || remaining.is_some_and(|r| r <= ERROR_LIMIT_LOW_WATER_MARK);
+    if budget_exhausted {
+        reset.map(Duration::from_secs)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,75 @@
+//! Access/refresh token handling: automatic refresh against EVE SSO, and
+//! pluggable persistence via [`TokenStore`].
+
+use crate::EsiError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// SSO endpoint used to exchange a refresh token for a new access token.
+pub(crate) const SSO_TOKEN_URL: &str = "https://login.eveonline.com/v2/oauth/token";
+
+/// The access/refresh token pair for an authenticated `Esi` client.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TokenSet {
+    /// The current access token, if any.
+    pub access_token: Option<String>,
+    /// Unix timestamp at which `access_token` expires.
+    pub access_expiration: Option<u64>,
+    /// The refresh token used to mint new access tokens.
+    pub refresh_token: Option<String>,
+}
+
+/// Persistence for a [`TokenSet`], so a refreshed token survives a restart
+/// without re-running the SSO authorization flow.
+pub trait TokenStore: fmt::Debug + Send + Sync {
+    /// Load a previously persisted token set, if one exists.
+    fn load(&self) -> Result<Option<TokenSet>, EsiError>;
+    /// Persist the given token set, replacing any previous one.
+    fn save(&self, tokens: &TokenSet) -> Result<(), EsiError>;
+}
+
+/// A [`TokenStore`] backed by a JSON file on disk.
+///
+/// Writes are atomic: the new token set is written to a temporary file in
+/// the same directory, then renamed over the destination, so a crash
+/// mid-write can never leave a truncated or corrupt file behind.
+#[derive(Debug)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    /// Use `path` as the backing file, read on [`TokenStore::load`] and
+    /// (over)written on [`TokenStore::save`].
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self) -> Result<Option<TokenSet>, EsiError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&self.path)?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<(), EsiError> {
+        let data = serde_json::to_string_pretty(tokens)?;
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Response body from [`SSO_TOKEN_URL`] on a successful refresh.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SsoTokenResponse {
+    pub(crate) access_token: String,
+    pub(crate) expires_in: u64,
+    pub(crate) refresh_token: Option<String>,
+}